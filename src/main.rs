@@ -1,24 +1,84 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
 
 use bevy::{
     asset::RenderAssetUsages,
-    input::mouse::MouseMotion,
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    input::mouse::{MouseMotion, MouseWheel},
+    pbr::wireframe::{WireframeConfig, WireframePlugin},
     prelude::*,
-    render::mesh::{Indices, PrimitiveTopology},
-    window::{CursorGrabMode, PrimaryWindow},
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_resource::WgpuFeatures,
+        renderer::RenderDevice,
+    },
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+    window::{CursorGrabMode, PresentMode, PrimaryWindow},
 };
 use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
 
+const WORLD_SEED: u32 = 1337;
 const CHUNK_SIZE: i32 = 16;
 const RENDER_DISTANCE_CHUNKS: i32 = 4;
-const MAX_CHUNK_GENERATES_PER_FRAME: usize = 2;
+const MAX_CHUNK_TASKS_IN_FLIGHT: usize = 16;
+/// Chunks further than this (in chunks, per axis) from the player use the coarse mesh.
+const LOD_DISTANCE_CHUNKS: i32 = 2;
 const MIN_HEIGHT: i32 = 2;
 const MAX_HEIGHT: i32 = 14;
+const BEDROCK_LEVEL: i32 = 0;
+const SEA_LEVEL: i32 = 6;
 const REACH_DISTANCE: f32 = 6.0;
+const BREAK_COOLDOWN: f32 = 0.15;
+const PLACE_COOLDOWN: f32 = 0.15;
 const PLAYER_SPEED: f32 = 9.0;
 const MOUSE_SENSITIVITY: f32 = 0.003;
+const MAX_PITCH: f32 = 1.54;
+const MIN_FOV_DEGREES: f32 = 20.0;
+const MAX_FOV_DEGREES: f32 = 110.0;
+const ZOOM_SPEED: f32 = 12.0;
 const PLAYER_AIR_RADIUS: i32 = 1;
 const PLAYER_AIR_HEIGHT: i32 = 2;
+const PLAYER_HALF_WIDTH: f32 = 0.3;
+const PLAYER_EYE_HEIGHT: f32 = 1.6;
+const PLAYER_HEAD_CLEARANCE: f32 = 0.2;
+const FLOOD_FILL_LIMIT: usize = 4096;
+const FLOOD_FILL_RADIUS: i32 = 24;
+const BOX_TOOL_MAX_VOLUME: usize = 16 * 16 * 16;
+const SAVE_PATH: &str = "world_save.bin";
+const AO_SHADES: [f32; 4] = [0.45, 0.65, 0.82, 1.0];
+const MAX_MOBS: usize = 8;
+const MOB_SPAWN_INTERVAL: f32 = 4.0;
+const MOB_SPAWN_MIN_DISTANCE: f32 = 10.0;
+const MOB_SPAWN_MAX_DISTANCE: f32 = 18.0;
+const MOB_SPEED: f32 = 2.5;
+const MOB_MAX_HEALTH: f32 = 20.0;
+const MOB_HIT_DAMAGE: f32 = 7.0;
+const MOB_HALF_WIDTH: f32 = 0.4;
+const MOB_HEIGHT: f32 = 1.6;
+const MOB_EYE_HEIGHT: f32 = 1.4;
+const MOB_SIGHT_RANGE: f32 = 24.0;
+const MOB_WANDER_INTERVAL: f32 = 3.0;
+const MOB_STUCK_TIME: f32 = 0.5;
+const MOB_UNSTUCK_DURATION: f32 = 1.2;
+const MIN_SIMULATION_SPEED: f32 = 0.125;
+const MAX_SIMULATION_SPEED: f32 = 4.0;
+const GRASS_SPREAD_INTERVAL: f32 = 0.5;
+const GRASS_SPREAD_SAMPLES: usize = 48;
+const MINIMAP_RADIUS: i32 = 16;
+const MINIMAP_BLOCKS_PER_CELL: i32 = 2;
+const MINIMAP_CELL_PIXELS: f32 = 3.0;
+const MINIMAP_REFRESH_INTERVAL: f32 = 0.25;
+const DAY_LENGTH_SECONDS: f32 = 600.0;
+const TIME_SKIP_HOURS: f32 = 1.0;
+const SUN_DISTANCE: f32 = 60.0;
+const SUN_ILLUMINANCE: f32 = 20_000.0;
+const DAY_AMBIENT_BRIGHTNESS: f32 = 450.0;
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 40.0;
+const DAY_SKY_COLOR: Vec3 = Vec3::new(0.55, 0.8, 0.95);
+const NIGHT_SKY_COLOR: Vec3 = Vec3::new(0.02, 0.03, 0.08);
 
 fn main() {
     App::new()
@@ -28,6 +88,21 @@ fn main() {
             brightness: 450.0,
         })
         .insert_resource(WorldBlocks::default())
+        .insert_resource(LookSettings::default())
+        .insert_resource(MovementSettings::default())
+        .insert_resource(FpsCounter::default())
+        .insert_resource(DebugOverlay::default())
+        .insert_resource(Hotbar::default())
+        .insert_resource(Inventory::default())
+        .insert_resource(GrassSpread::default())
+        .insert_resource(Minimap::default())
+        .insert_resource(CrosshairSettings::default())
+        .insert_resource(BoxTool::default())
+        .insert_resource(MobSpawner::default())
+        .insert_resource(DifficultySettings::default())
+        .insert_resource(InteractionSettings::default())
+        .insert_resource(InteractionCooldowns::default())
+        .insert_resource(TimeOfDay::default())
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "RustCraft (Bevy)".to_string(),
@@ -35,15 +110,51 @@ fn main() {
             }),
             ..default()
         }))
+        .add_plugins((FrameTimeDiagnosticsPlugin, WireframePlugin))
         .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                save_world,
+                load_world,
+                reset_world,
+                simulation_controls,
+                update_time_of_day,
+                toggle_wireframe,
+                toggle_vsync,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                cycle_difficulty,
+                spawn_mobs,
+                move_mobs,
+                attack_mobs.before(block_interaction),
+            ),
+        )
         .add_systems(
             Update,
             (
                 lock_cursor_on_click,
                 player_look,
+                update_fov,
                 player_movement,
                 stream_world_around_player,
+                finish_chunk_generation,
+                rebuild_stale_chunk_meshes.after(stream_world_around_player),
+                update_chunk_lods,
+                spread_grass,
+                select_hotbar_slot,
+                toggle_creative_mode,
+                update_hotbar,
                 block_interaction,
+                draw_block_highlight,
+                draw_box_tool_preview,
+                update_crosshair,
+                update_fps_counter,
+                update_debug_overlay,
+                update_minimap,
             ),
         )
         .run();
@@ -52,28 +163,372 @@ fn main() {
 #[derive(Default)]
 struct ChunkData {
     entity: Option<Entity>,
+    water_entity: Option<Entity>,
     blocks: Vec<IVec3>,
+    /// Detail level the current mesh was built at.
+    lod: ChunkLod,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum ChunkLod {
+    #[default]
+    Full,
+    Coarse,
 }
 
 #[derive(Resource, Default)]
 struct WorldBlocks {
     map: HashMap<IVec3, BlockType>,
     chunks: HashMap<IVec2, ChunkData>,
+    /// Player edits on top of the procedural terrain; `None` marks a removed block.
+    edits: HashMap<IVec3, Option<BlockType>>,
+    /// Chunk the player is in, used to pick each chunk's mesh detail.
+    center_chunk: IVec2,
+    /// Loaded chunks whose neighbours changed, so their boundary faces need re-culling.
+    stale_meshes: HashSet<IVec2>,
+}
+
+impl WorldBlocks {
+    /// Sets a block and tracks it in its chunk's block list exactly once. Refuses positions in
+    /// chunks that haven't been generated, so no orphaned chunk data is ever created.
+    fn insert_block(&mut self, position: IVec3, block_type: BlockType) -> bool {
+        let Some(chunk_data) = self.chunks.get_mut(&world_to_chunk(position)) else {
+            return false;
+        };
+        if !chunk_data.blocks.contains(&position) {
+            chunk_data.blocks.push(position);
+        }
+
+        self.map.insert(position, block_type);
+        self.edits.insert(position, Some(block_type));
+        true
+    }
+
+    /// Replaces the face-connected region of the block type found at `start`, bounded by
+    /// `FLOOD_FILL_RADIUS` and `FLOOD_FILL_LIMIT`. Returns the chunks that changed.
+    fn flood_fill(&mut self, start: IVec3, replacement: BlockType) -> HashSet<IVec2> {
+        let mut dirty_chunks = HashSet::new();
+        let Some(target) = self.map.get(&start).copied() else {
+            return dirty_chunks;
+        };
+        if target == replacement || target == BlockType::Bedrock {
+            return dirty_chunks;
+        }
+
+        let mut queue = VecDeque::from([start]);
+        let mut visited = HashSet::from([start]);
+        let mut changed = 0;
+
+        while let Some(position) = queue.pop_front() {
+            if changed >= FLOOD_FILL_LIMIT {
+                break;
+            }
+            if self.map.get(&position) != Some(&target) {
+                continue;
+            }
+
+            self.map.insert(position, replacement);
+            self.edits.insert(position, Some(replacement));
+            dirty_chunks.insert(world_to_chunk(position));
+            changed += 1;
+
+            for normal in [
+                IVec3::X,
+                IVec3::NEG_X,
+                IVec3::Y,
+                IVec3::NEG_Y,
+                IVec3::Z,
+                IVec3::NEG_Z,
+            ] {
+                let next = position + normal;
+                let offset = (next - start).abs();
+                if offset.max_element() <= FLOOD_FILL_RADIUS && visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        dirty_chunks
+    }
+
+    /// Fills (or clears, with `None`) the inclusive box between two corners, leaving bedrock
+    /// untouched. Returns the chunks whose geometry changed.
+    fn fill_box(
+        &mut self,
+        corner_a: IVec3,
+        corner_b: IVec3,
+        block_type: Option<BlockType>,
+    ) -> HashSet<IVec2> {
+        let mut dirty_chunks = HashSet::new();
+        let min = corner_a.min(corner_b);
+        let max = corner_a.max(corner_b);
+        if box_volume(min, max) > BOX_TOOL_MAX_VOLUME {
+            return dirty_chunks;
+        }
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let position = IVec3::new(x, y, z);
+                    if matches!(self.map.get(&position), Some(BlockType::Bedrock)) {
+                        continue;
+                    }
+
+                    let changed = match block_type {
+                        Some(block_type) => self.insert_block(position, block_type),
+                        None => self.remove_block(position).is_some(),
+                    };
+                    if changed {
+                        dirty_chunks.extend(chunk_neighbors_inclusive(world_to_chunk(position)));
+                    }
+                }
+            }
+        }
+
+        dirty_chunks
+    }
+
+    fn remove_block(&mut self, position: IVec3) -> Option<BlockType> {
+        let removed = self.map.remove(&position)?;
+        self.edits.insert(position, None);
+        if let Some(chunk_data) = self.chunks.get_mut(&world_to_chunk(position)) {
+            chunk_data.blocks.retain(|&p| p != position);
+        }
+        Some(removed)
+    }
+}
+
+/// On-disk form of the player's edits; unedited terrain is regenerated from the seed.
+#[derive(Serialize, Deserialize)]
+struct SavedWorld {
+    /// Seed the edits were made on; they only make sense on top of the same terrain.
+    seed: u32,
+    edits: Vec<([i32; 3], Option<BlockType>)>,
+}
+
+#[derive(Resource, Default)]
+struct Inventory {
+    counts: HashMap<BlockType, u32>,
+    creative: bool,
+}
+
+impl Inventory {
+    fn add(&mut self, block_type: BlockType) {
+        *self.counts.entry(block_type).or_insert(0) += 1;
+    }
+
+    /// Consumes one block for placement; always succeeds in creative mode.
+    fn take(&mut self, block_type: BlockType) -> bool {
+        if self.creative {
+            return true;
+        }
+
+        match self.counts.get_mut(&block_type) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Resource)]
 struct WorldGenerator {
+    seed: u32,
     noise: Perlin,
     generated_chunks: HashSet<IVec2>,
+    /// Chunks currently being generated, mapped to the entity holding their task.
+    pending_chunks: HashMap<IVec2, Entity>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Component)]
+struct ChunkGenerationTask {
+    chunk: IVec2,
+    task: Task<Vec<(IVec3, BlockType)>>,
+}
+
+/// Tiny xorshift generator; good enough for picking cells to sample and spawn offsets.
+struct XorShift(u32);
+
+impl XorShift {
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform float in `[0, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+#[derive(Resource)]
+struct GrassSpread {
+    timer: Timer,
+    rng: XorShift,
+}
+
+impl Default for GrassSpread {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(GRASS_SPREAD_INTERVAL, TimerMode::Repeating),
+            rng: XorShift(0x9e37_79b9),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Mob {
+    health: f32,
+    /// Heading used while the player is out of sight or while getting unstuck.
+    wander_direction: Vec3,
+    wander_timer: f32,
+    /// How long the mob has been blocked without moving.
+    stuck_time: f32,
+    /// Remaining time the mob ignores the player to walk around an obstacle.
+    unstuck_timer: f32,
+}
+
+impl Mob {
+    fn new(health: f32) -> Self {
+        Self {
+            health,
+            wander_direction: Vec3::ZERO,
+            wander_timer: 0.0,
+            stuck_time: 0.0,
+            unstuck_timer: 0.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MobSpawner {
+    timer: Timer,
+    rng: XorShift,
+}
+
+impl Default for MobSpawner {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(MOB_SPAWN_INTERVAL, TimerMode::Repeating),
+            rng: XorShift(0x2545_f491),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Difficulty {
+    Peaceful,
+    Normal,
+    Hard,
+}
+
+#[derive(Resource)]
+struct DifficultySettings {
+    preset: Difficulty,
+    max_mobs: usize,
+    /// Seconds between spawn attempts.
+    spawn_interval: f32,
+    health_multiplier: f32,
+    speed_multiplier: f32,
+    /// How far mobs can spot the player; beyond it (or behind terrain) they wander.
+    sight_range: f32,
+}
+
+impl DifficultySettings {
+    fn from_preset(preset: Difficulty) -> Self {
+        match preset {
+            Difficulty::Peaceful => Self {
+                preset,
+                max_mobs: 0,
+                spawn_interval: MOB_SPAWN_INTERVAL,
+                health_multiplier: 1.0,
+                speed_multiplier: 1.0,
+                sight_range: MOB_SIGHT_RANGE,
+            },
+            Difficulty::Normal => Self {
+                preset,
+                max_mobs: MAX_MOBS,
+                spawn_interval: MOB_SPAWN_INTERVAL,
+                health_multiplier: 1.0,
+                speed_multiplier: 1.0,
+                sight_range: MOB_SIGHT_RANGE,
+            },
+            Difficulty::Hard => Self {
+                preset,
+                max_mobs: MAX_MOBS * 2,
+                spawn_interval: MOB_SPAWN_INTERVAL * 0.35,
+                health_multiplier: 2.0,
+                speed_multiplier: 1.4,
+                sight_range: MOB_SIGHT_RANGE * 1.5,
+            },
+        }
+    }
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self::from_preset(Difficulty::Normal)
+    }
+}
+
+#[derive(Resource)]
+struct TimeOfDay {
+    /// Hour of the day in `0.0..24.0`; 6 is sunrise, 12 is noon and 18 is sunset.
+    hour: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self { hour: 10.0 }
+    }
+}
+
+impl TimeOfDay {
+    /// Direction from the world towards the sun.
+    fn sun_direction(&self) -> Vec3 {
+        let angle = (self.hour - 6.0) / 24.0 * std::f32::consts::TAU;
+        Vec3::new(angle.cos(), angle.sin(), 0.35).normalize()
+    }
+
+    /// 0 at night, ramping up to 1 once the sun is well above the horizon.
+    fn daylight(&self) -> f32 {
+        (self.sun_direction().y * 3.0 + 0.2).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+#[derive(Resource)]
+struct MobRenderResources {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum BlockType {
     Grass,
     Dirt,
     Stone,
+    Bedrock,
+    Water,
+}
+
+impl BlockType {
+    /// Transparent blocks are meshed into the alpha-blended pass and never hide neighbouring faces.
+    fn is_transparent(self) -> bool {
+        matches!(self, BlockType::Water)
+    }
 }
 
+const PLACEABLE_BLOCKS: [BlockType; 3] = [BlockType::Grass, BlockType::Dirt, BlockType::Stone];
+
 #[derive(Component)]
 struct BlockChunk;
 
@@ -81,14 +536,168 @@ struct BlockChunk;
 struct Player {
     yaw: f32,
     pitch: f32,
+    target_yaw: f32,
+    target_pitch: f32,
+}
+
+#[derive(Resource)]
+struct LookSettings {
+    sensitivity_x: f32,
+    sensitivity_y: f32,
+    invert_y: bool,
+    /// Time constant in seconds for easing towards the mouse target; 0 applies raw input.
+    smoothing: f32,
+    fov_degrees: f32,
+    zoom_fov_degrees: f32,
 }
 
+impl Default for LookSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity_x: MOUSE_SENSITIVITY,
+            sensitivity_y: MOUSE_SENSITIVITY,
+            invert_y: false,
+            smoothing: 0.0,
+            fov_degrees: 45.0,
+            zoom_fov_degrees: 25.0,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MovementSettings {
+    /// Keep W/S on the horizontal plane; off by default so they follow the full look direction.
+    planar: bool,
+}
+
+#[derive(Resource)]
+struct InteractionSettings {
+    reach: f32,
+    /// Minimum seconds between accepted break clicks.
+    break_cooldown: f32,
+    /// Minimum seconds between accepted place clicks.
+    place_cooldown: f32,
+}
+
+impl Default for InteractionSettings {
+    fn default() -> Self {
+        Self {
+            reach: REACH_DISTANCE,
+            break_cooldown: BREAK_COOLDOWN,
+            place_cooldown: PLACE_COOLDOWN,
+        }
+    }
+}
+
+/// Elapsed-time stamps of the last accepted break and place clicks.
+#[derive(Resource)]
+struct InteractionCooldowns {
+    last_break: f32,
+    last_place: f32,
+}
+
+impl Default for InteractionCooldowns {
+    fn default() -> Self {
+        Self {
+            last_break: f32::NEG_INFINITY,
+            last_place: f32::NEG_INFINITY,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct BoxTool {
+    first_corner: Option<IVec3>,
+}
+
+#[derive(Resource, Default)]
+struct Hotbar {
+    selected: usize,
+}
+
+impl Hotbar {
+    fn selected_block(&self) -> BlockType {
+        PLACEABLE_BLOCKS[self.selected]
+    }
+}
+
+#[derive(Component)]
+struct HotbarSlot(usize);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CrosshairStyle {
+    Dot,
+    Cross,
+    Circle,
+}
+
+#[derive(Resource)]
+struct CrosshairSettings {
+    style: CrosshairStyle,
+    /// Overall extent of the crosshair in logical pixels.
+    size: f32,
+    thickness: f32,
+    color: Color,
+}
+
+impl Default for CrosshairSettings {
+    fn default() -> Self {
+        Self {
+            style: CrosshairStyle::Dot,
+            size: 4.0,
+            thickness: 2.0,
+            color: Color::BLACK.with_alpha(0.75),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Crosshair;
+
+#[derive(Resource, Default)]
+struct FpsCounter {
+    show_frame_time: bool,
+}
+
+#[derive(Component)]
+struct FpsText;
+
+#[derive(Resource, Default)]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+struct DebugText;
+
+#[derive(Resource)]
+struct Minimap {
+    timer: Timer,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(MINIMAP_REFRESH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// One minimap pixel, offset in cells from the player; north (-Z) is up.
+#[derive(Component)]
+struct MinimapCell(IVec2);
+
 #[derive(Resource)]
 struct BlockRenderResources {
     material: Handle<StandardMaterial>,
+    water_material: Handle<StandardMaterial>,
 }
 
-fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
     let block_material = materials.add(StandardMaterial {
         base_color: Color::WHITE,
         perceptual_roughness: 0.95,
@@ -96,24 +705,50 @@ fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>
     });
 
     commands.insert_resource(WorldGenerator {
-        noise: Perlin::new(1337),
+        seed: WORLD_SEED,
+        noise: Perlin::new(WORLD_SEED),
         generated_chunks: HashSet::new(),
+        pending_chunks: HashMap::new(),
+    });
+
+    let water_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.2,
+        ..default()
     });
 
     commands.insert_resource(BlockRenderResources {
         material: block_material,
+        water_material,
     });
 
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            shadows_enabled: true,
-            illuminance: 20_000.0,
+    commands.insert_resource(MobRenderResources {
+        mesh: meshes.add(Cuboid::new(
+            MOB_HALF_WIDTH * 2.0,
+            MOB_HEIGHT,
+            MOB_HALF_WIDTH * 2.0,
+        )),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.75, 0.2, 0.2),
+            perceptual_roughness: 0.8,
             ..default()
-        },
-        transform: Transform::from_xyz(20.0, 40.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
+        }),
     });
 
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                illuminance: SUN_ILLUMINANCE,
+                ..default()
+            },
+            transform: Transform::from_xyz(20.0, 40.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        Sun,
+    ));
+
     let camera_transform =
         Transform::from_xyz(0.0, 18.0, 24.0).looking_at(Vec3::new(0.0, 5.0, 0.0), Vec3::Y);
 
@@ -124,11 +759,16 @@ fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>
             transform: camera_transform,
             ..default()
         },
-        Player { yaw, pitch },
+        Player {
+            yaw,
+            pitch,
+            target_yaw: yaw,
+            target_pitch: pitch,
+        },
     ));
 
-    commands
-        .spawn(NodeBundle {
+    commands.spawn((
+        NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
@@ -138,30 +778,125 @@ fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>
             },
             background_color: Color::NONE.into(),
             ..default()
-        })
-        .with_children(|parent| {
-            parent.spawn(NodeBundle {
-                style: Style {
-                    width: Val::Px(4.0),
-                    height: Val::Px(4.0),
+        },
+        Crosshair,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        FpsText,
+    ));
+
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
                     ..default()
                 },
-                background_color: Color::BLACK.with_alpha(0.75).into(),
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
                 ..default()
-            });
+            })
+        },
+        DebugText,
+    ));
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(16.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (index, &block_type) in PLACEABLE_BLOCKS.iter().enumerate() {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(40.0),
+                            height: Val::Px(40.0),
+                            border: UiRect::all(Val::Px(3.0)),
+                            ..default()
+                        },
+                        background_color: block_color(block_type).into(),
+                        border_color: Color::BLACK.with_alpha(0.5).into(),
+                        ..default()
+                    },
+                    HotbarSlot(index),
+                ));
+            }
+        });
+
+    let minimap_size = (MINIMAP_RADIUS * 2 + 1) as f32 * MINIMAP_CELL_PIXELS;
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(36.0),
+                right: Val::Px(10.0),
+                width: Val::Px(minimap_size),
+                height: Val::Px(minimap_size),
+                flex_wrap: FlexWrap::Wrap,
+                ..default()
+            },
+            background_color: Color::BLACK.with_alpha(0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for z in -MINIMAP_RADIUS..=MINIMAP_RADIUS {
+                for x in -MINIMAP_RADIUS..=MINIMAP_RADIUS {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(MINIMAP_CELL_PIXELS),
+                                height: Val::Px(MINIMAP_CELL_PIXELS),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        MinimapCell(IVec2::new(x, z)),
+                    ));
+                }
+            }
         });
 }
 
 fn stream_world_around_player(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut world: ResMut<WorldBlocks>,
     mut world_gen: ResMut<WorldGenerator>,
-    render: Res<BlockRenderResources>,
     player: Query<&Transform, With<Player>>,
 ) {
     let player_pos = player.single().translation.round().as_ivec3();
     let center_chunk = world_to_chunk(player_pos);
+    if world.center_chunk != center_chunk {
+        world.center_chunk = center_chunk;
+    }
 
     let mut required_chunks = HashSet::new();
     for cx in -RENDER_DISTANCE_CHUNKS..=RENDER_DISTANCE_CHUNKS {
@@ -170,20 +905,40 @@ fn stream_world_around_player(
         }
     }
 
-    let mut generated_this_frame = 0;
-    for &chunk in &required_chunks {
-        if generated_this_frame >= MAX_CHUNK_GENERATES_PER_FRAME {
-            break;
-        }
-        if world_gen.generated_chunks.contains(&chunk) {
-            continue;
+    let mut missing_chunks: Vec<IVec2> = required_chunks
+        .iter()
+        .copied()
+        .filter(|chunk| {
+            !world_gen.generated_chunks.contains(chunk)
+                && !world_gen.pending_chunks.contains_key(chunk)
+        })
+        .collect();
+    missing_chunks.sort_by_key(|chunk| (*chunk - center_chunk).length_squared());
+
+    let task_pool = AsyncComputeTaskPool::get();
+    for chunk in missing_chunks {
+        if world_gen.pending_chunks.len() >= MAX_CHUNK_TASKS_IN_FLIGHT {
+            break;
         }
 
-        generate_chunk(&mut world, &world_gen, chunk, player_pos);
-        world_gen.generated_chunks.insert(chunk);
-        generated_this_frame += 1;
+        let noise = world_gen.noise;
+        let task = task_pool.spawn(async move { generate_chunk(&noise, chunk, player_pos) });
+        let entity = commands.spawn(ChunkGenerationTask { chunk, task }).id();
+        world_gen.pending_chunks.insert(chunk, entity);
+    }
 
-        rebuild_chunk_and_neighbors(&mut commands, &mut meshes, &mut world, &render, chunk);
+    let cancelled_chunks: Vec<IVec2> = world_gen
+        .pending_chunks
+        .keys()
+        .copied()
+        .filter(|chunk| !required_chunks.contains(chunk))
+        .collect();
+
+    for chunk in cancelled_chunks {
+        // Dropping the task along with its entity cancels the generation.
+        if let Some(entity) = world_gen.pending_chunks.remove(&chunk) {
+            commands.entity(entity).despawn();
+        }
     }
 
     let obsolete_chunks: Vec<IVec2> = world_gen
@@ -196,6 +951,73 @@ fn stream_world_around_player(
     for chunk in obsolete_chunks {
         unload_chunk(&mut commands, &mut world, &mut world_gen, chunk);
     }
+
+    // Chunk data that outlived its generation (or never had one) is dropped along with any
+    // blocks it still tracks.
+    let orphaned_chunks: Vec<IVec2> = world
+        .chunks
+        .keys()
+        .copied()
+        .filter(|chunk| !world_gen.generated_chunks.contains(chunk))
+        .collect();
+
+    for chunk in orphaned_chunks {
+        unload_chunk(&mut commands, &mut world, &mut world_gen, chunk);
+    }
+}
+
+fn update_chunk_lods(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut world: ResMut<WorldBlocks>,
+    render: Res<BlockRenderResources>,
+) {
+    let stale_chunks: Vec<IVec2> = world
+        .chunks
+        .iter()
+        .filter(|(chunk, data)| data.lod != chunk_lod(**chunk, world.center_chunk))
+        .map(|(chunk, _)| *chunk)
+        .collect();
+
+    for chunk in stale_chunks {
+        rebuild_chunk_mesh(&mut commands, &mut meshes, &mut world, &render, chunk);
+    }
+}
+
+fn chunk_lod(chunk: IVec2, center_chunk: IVec2) -> ChunkLod {
+    let distance = (chunk - center_chunk).abs().max_element();
+    if distance > LOD_DISTANCE_CHUNKS {
+        ChunkLod::Coarse
+    } else {
+        ChunkLod::Full
+    }
+}
+
+fn finish_chunk_generation(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut world: ResMut<WorldBlocks>,
+    mut world_gen: ResMut<WorldGenerator>,
+    render: Res<BlockRenderResources>,
+    mut tasks: Query<(Entity, &mut ChunkGenerationTask)>,
+) {
+    for (entity, mut generation) in &mut tasks {
+        let Some(blocks) = block_on(future::poll_once(&mut generation.task)) else {
+            continue;
+        };
+
+        let chunk = generation.chunk;
+        commands.entity(entity).despawn();
+        if world_gen.pending_chunks.get(&chunk) != Some(&entity) {
+            continue;
+        }
+        world_gen.pending_chunks.remove(&chunk);
+
+        insert_generated_chunk(&mut world, chunk, blocks);
+        world_gen.generated_chunks.insert(chunk);
+
+        rebuild_chunk_and_neighbors(&mut commands, &mut meshes, &mut world, &render, chunk);
+    }
 }
 
 fn world_to_chunk(position: IVec3) -> IVec2 {
@@ -209,29 +1031,29 @@ fn chunk_to_world_min(chunk: IVec2) -> IVec2 {
     IVec2::new(chunk.x * CHUNK_SIZE, chunk.y * CHUNK_SIZE)
 }
 
-fn generate_chunk(
-    world: &mut WorldBlocks,
-    world_gen: &WorldGenerator,
-    chunk: IVec2,
-    player_position: IVec3,
-) {
+/// Produces the terrain blocks of one chunk. Runs on the async compute pool, so it only
+/// touches the noise it is handed.
+fn generate_chunk(noise: &Perlin, chunk: IVec2, player_position: IVec3) -> Vec<(IVec3, BlockType)> {
     let min = chunk_to_world_min(chunk);
-    let mut positions = Vec::with_capacity((CHUNK_SIZE * CHUNK_SIZE * (MAX_HEIGHT + 1)) as usize);
+    let mut blocks = Vec::with_capacity((CHUNK_SIZE * CHUNK_SIZE * (MAX_HEIGHT + 1)) as usize);
 
     for x in min.x..(min.x + CHUNK_SIZE) {
         for z in min.y..(min.y + CHUNK_SIZE) {
-            let sample = world_gen.noise.get([x as f64 * 0.08, z as f64 * 0.08]) as f32;
+            let sample = noise.get([x as f64 * 0.08, z as f64 * 0.08]) as f32;
             let normalized = (sample + 1.0) * 0.5;
             let height =
                 MIN_HEIGHT + ((MAX_HEIGHT - MIN_HEIGHT) as f32 * normalized).round() as i32;
 
             for y in 0..=height {
                 let position = IVec3::new(x, y, z);
-                if is_player_air_cell(position, player_position) {
+                let bedrock = is_bedrock_cell(noise, position);
+                if !bedrock && is_player_air_cell(position, player_position) {
                     continue;
                 }
 
-                let block_type = if y == height {
+                let block_type = if bedrock {
+                    BlockType::Bedrock
+                } else if y == height {
                     BlockType::Grass
                 } else if y > height - 3 {
                     BlockType::Dirt
@@ -239,8 +1061,43 @@ fn generate_chunk(
                     BlockType::Stone
                 };
 
-                world.map.insert(position, block_type);
-                positions.push(position);
+                blocks.push((position, block_type));
+            }
+
+            for y in (height + 1)..=SEA_LEVEL {
+                let position = IVec3::new(x, y, z);
+                if !is_player_air_cell(position, player_position) {
+                    blocks.push((position, BlockType::Water));
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+fn insert_generated_chunk(world: &mut WorldBlocks, chunk: IVec2, blocks: Vec<(IVec3, BlockType)>) {
+    let mut positions = Vec::with_capacity(blocks.len());
+    for (position, block_type) in blocks {
+        world.map.insert(position, block_type);
+        positions.push(position);
+    }
+
+    // Re-apply the player's edits so they survive the chunk being unloaded or reloaded.
+    for (&position, &edit) in &world.edits {
+        if world_to_chunk(position) != chunk {
+            continue;
+        }
+        match edit {
+            Some(block_type) => {
+                if world.map.insert(position, block_type).is_none() {
+                    positions.push(position);
+                }
+            }
+            None => {
+                if world.map.remove(&position).is_some() {
+                    positions.retain(|&p| p != position);
+                }
             }
         }
     }
@@ -250,11 +1107,24 @@ fn generate_chunk(
         .entry(chunk)
         .and_modify(|data| data.blocks = positions.clone())
         .or_insert(ChunkData {
-            entity: None,
             blocks: positions,
+            ..default()
         });
 }
 
+fn is_bedrock_cell(noise: &Perlin, position: IVec3) -> bool {
+    if position.y == BEDROCK_LEVEL {
+        return true;
+    }
+    if position.y != BEDROCK_LEVEL + 1 {
+        return false;
+    }
+
+    // Ragged second layer so the floor doesn't read as a perfectly flat plane.
+    let sample = noise.get([position.x as f64 * 0.45, position.z as f64 * 0.45, 0.5]);
+    sample > 0.1
+}
+
 fn unload_chunk(
     commands: &mut Commands,
     world: &mut WorldBlocks,
@@ -262,18 +1132,41 @@ fn unload_chunk(
     chunk: IVec2,
 ) {
     if let Some(chunk_data) = world.chunks.remove(&chunk) {
-        if let Some(entity) = chunk_data.entity {
+        for entity in [chunk_data.entity, chunk_data.water_entity]
+            .into_iter()
+            .flatten()
+        {
             commands.entity(entity).despawn_recursive();
         }
 
         for position in chunk_data.blocks {
             world.map.remove(&position);
         }
+
+        world
+            .stale_meshes
+            .extend(chunk_neighbors_inclusive(chunk).into_iter().skip(1));
     }
 
     world_gen.generated_chunks.remove(&chunk);
 }
 
+fn rebuild_stale_chunk_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut world: ResMut<WorldBlocks>,
+    render: Res<BlockRenderResources>,
+) {
+    if world.stale_meshes.is_empty() {
+        return;
+    }
+
+    let stale: Vec<IVec2> = world.stale_meshes.drain().collect();
+    for chunk in stale {
+        rebuild_chunk_mesh(&mut commands, &mut meshes, &mut world, &render, chunk);
+    }
+}
+
 fn chunk_neighbors_inclusive(chunk: IVec2) -> [IVec2; 5] {
     [
         chunk,
@@ -307,135 +1200,283 @@ fn rebuild_chunk_mesh(
         return;
     };
 
-    let mesh = build_chunk_mesh(&world.map, &chunk_data.blocks);
+    chunk_data.lod = chunk_lod(chunk, world.center_chunk);
+    let opaque = match chunk_data.lod {
+        ChunkLod::Full => build_chunk_mesh(&world.map, &chunk_data.blocks, false),
+        ChunkLod::Coarse => build_coarse_chunk_mesh(&world.map, &chunk_data.blocks),
+    };
+    let water = build_chunk_mesh(&world.map, &chunk_data.blocks, true);
 
-    if let Some(existing_entity) = chunk_data.entity.take() {
+    for existing_entity in [chunk_data.entity.take(), chunk_data.water_entity.take()]
+        .into_iter()
+        .flatten()
+    {
         commands.entity(existing_entity).despawn_recursive();
     }
 
-    if let Some(mesh) = mesh {
-        let mesh_handle = meshes.add(mesh);
-        let entity = commands
-            .spawn((
-                PbrBundle {
-                    mesh: mesh_handle,
-                    material: render.material.clone(),
-                    ..default()
-                },
-                BlockChunk,
-            ))
-            .id();
-        chunk_data.entity = Some(entity);
-    }
+    chunk_data.entity =
+        opaque.map(|mesh| spawn_chunk_mesh(commands, meshes, chunk, mesh, &render.material));
+    chunk_data.water_entity =
+        water.map(|mesh| spawn_chunk_mesh(commands, meshes, chunk, mesh, &render.water_material));
 }
 
-fn build_chunk_mesh(map: &HashMap<IVec3, BlockType>, blocks: &[IVec3]) -> Option<Mesh> {
-    if blocks.is_empty() {
-        return None;
-    }
+fn spawn_chunk_mesh(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    chunk: IVec2,
+    mesh: Mesh,
+    material: &Handle<StandardMaterial>,
+) -> Entity {
+    // Place the entity at the chunk centre so Bevy's back-to-front sort of transparent
+    // meshes orders overlapping water chunks by their actual distance to the camera.
+    let min = chunk_to_world_min(chunk);
+    let half = CHUNK_SIZE as f32 / 2.0;
+    let origin = Vec3::new(min.x as f32 + half, SEA_LEVEL as f32, min.y as f32 + half);
 
-    let mut positions: Vec<[f32; 3]> = Vec::new();
-    let mut normals: Vec<[f32; 3]> = Vec::new();
-    let mut colors: Vec<[f32; 4]> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: meshes.add(mesh.translated_by(-origin)),
+                material: material.clone(),
+                transform: Transform::from_translation(origin),
+                ..default()
+            },
+            BlockChunk,
+        ))
+        .id()
+}
+
+/// Builds either the opaque or the transparent (water) mesh for a chunk's blocks.
+fn build_chunk_mesh(
+    map: &HashMap<IVec3, BlockType>,
+    blocks: &[IVec3],
+    transparent: bool,
+) -> Option<Mesh> {
+    let mut builder = ChunkMeshBuilder::default();
 
     for &pos in blocks {
         let Some(block_type) = map.get(&pos).copied() else {
             continue;
         };
+        if block_type.is_transparent() != transparent {
+            continue;
+        }
 
         for (normal, face) in cube_faces(pos) {
-            if map.contains_key(&(pos + normal)) {
+            // Water hides against anything; opaque faces stay visible behind water.
+            let hidden = match map.get(&(pos + normal)) {
+                Some(neighbor) => transparent || !neighbor.is_transparent(),
+                None => false,
+            };
+            if hidden {
                 continue;
             }
 
-            let base = positions.len() as u32;
-            let n = normal.as_vec3();
-            let color = block_color(block_type).to_linear().to_f32_array();
+            let ao = face.map(|vertex| vertex_ao(map, pos, normal, vertex));
+            builder.push_face(face, normal, block_color(block_type), ao);
+        }
+    }
+
+    builder.build()
+}
+
+/// Builds a low-detail opaque mesh for distant chunks by merging each 2x2x2 group of blocks
+/// into one cube coloured after its topmost block.
+fn build_coarse_chunk_mesh(map: &HashMap<IVec3, BlockType>, blocks: &[IVec3]) -> Option<Mesh> {
+    let mut cells: HashMap<IVec3, (i32, BlockType)> = HashMap::new();
+    for &pos in blocks {
+        let Some(block_type) = map.get(&pos).copied() else {
+            continue;
+        };
+        if block_type.is_transparent() {
+            continue;
+        }
+
+        let cell = pos.div_euclid(IVec3::splat(2));
+        let top = cells.entry(cell).or_insert((pos.y, block_type));
+        if pos.y > top.0 {
+            *top = (pos.y, block_type);
+        }
+    }
+
+    let coarse_occupied = |cell: IVec3| {
+        cells.contains_key(&cell)
+            || (0..8).any(|i| {
+                let offset = IVec3::new(i & 1, (i >> 1) & 1, (i >> 2) & 1);
+                is_opaque_at(map, cell * 2 + offset)
+            })
+    };
 
-            for vertex in face {
-                positions.push(vertex);
-                normals.push([n.x, n.y, n.z]);
-                colors.push(color);
+    let mut builder = ChunkMeshBuilder::default();
+    for (&cell, &(_, block_type)) in &cells {
+        let center = cell.as_vec3() * 2.0 + Vec3::splat(0.5);
+        for (normal, face) in scaled_cube_faces(center, 1.0) {
+            if !coarse_occupied(cell + normal) {
+                builder.push_face(face, normal, block_color(block_type), [3; 4]);
             }
+        }
+    }
+
+    builder.build()
+}
+
+#[derive(Default)]
+struct ChunkMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl ChunkMeshBuilder {
+    fn push_face(&mut self, face: [[f32; 3]; 4], normal: IVec3, color: Color, ao: [usize; 4]) {
+        let base = self.positions.len() as u32;
+        let n = normal.as_vec3();
+        let color = color.to_linear().to_f32_array();
+
+        for (vertex, level) in face.into_iter().zip(ao) {
+            let shade = AO_SHADES[level];
+            self.positions.push(vertex);
+            self.normals.push([n.x, n.y, n.z]);
+            self.colors.push([
+                color[0] * shade,
+                color[1] * shade,
+                color[2] * shade,
+                color[3],
+            ]);
+        }
 
-            indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+        // Split the quad along the brighter diagonal so AO interpolates without creases.
+        if ao[0] + ao[2] >= ao[1] + ao[3] {
+            self.indices
+                .extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+        } else {
+            self.indices.extend_from_slice(&[
+                base,
+                base + 3,
+                base + 1,
+                base + 1,
+                base + 3,
+                base + 2,
+            ]);
         }
     }
 
-    if indices.is_empty() {
-        return None;
+    fn build(self) -> Option<Mesh> {
+        if self.indices.is_empty() {
+            return None;
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh.insert_indices(Indices::U32(self.indices));
+        Some(mesh)
+    }
+}
+
+/// Ambient occlusion level (0 = darkest, 3 = unoccluded) for one corner of a face, from the
+/// two edge neighbours and the diagonal neighbour in the layer the face looks into.
+fn vertex_ao(
+    map: &HashMap<IVec3, BlockType>,
+    block: IVec3,
+    normal: IVec3,
+    vertex: [f32; 3],
+) -> usize {
+    let offset = Vec3::from_array(vertex) - block.as_vec3();
+    let layer = block + normal;
+
+    let mut tangents = [IVec3::ZERO; 2];
+    let mut count = 0;
+    for axis in 0..3 {
+        if normal[axis] == 0 {
+            tangents[count][axis] = if offset[axis] > 0.0 { 1 } else { -1 };
+            count += 1;
+        }
     }
 
-    let mut mesh = Mesh::new(
-        PrimitiveTopology::TriangleList,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-    );
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
-    mesh.insert_indices(Indices::U32(indices));
-    Some(mesh)
+    let side_a = is_opaque_at(map, layer + tangents[0]);
+    let side_b = is_opaque_at(map, layer + tangents[1]);
+    let corner = is_opaque_at(map, layer + tangents[0] + tangents[1]);
+
+    if side_a && side_b {
+        0
+    } else {
+        3 - (side_a as usize + side_b as usize + corner as usize)
+    }
+}
+
+fn is_opaque_at(map: &HashMap<IVec3, BlockType>, position: IVec3) -> bool {
+    map.get(&position)
+        .is_some_and(|block_type| !block_type.is_transparent())
 }
 
 fn cube_faces(position: IVec3) -> [(IVec3, [[f32; 3]; 4]); 6] {
-    let x = position.x as f32;
-    let y = position.y as f32;
-    let z = position.z as f32;
+    scaled_cube_faces(position.as_vec3(), 0.5)
+}
+
+/// Faces of an axis-aligned cube with the given centre and half extent, each paired with its
+/// outward normal.
+fn scaled_cube_faces(center: Vec3, half: f32) -> [(IVec3, [[f32; 3]; 4]); 6] {
+    let Vec3 { x, y, z } = center;
 
     [
         (
             IVec3::X,
             [
-                [x + 0.5, y - 0.5, z - 0.5],
-                [x + 0.5, y - 0.5, z + 0.5],
-                [x + 0.5, y + 0.5, z + 0.5],
-                [x + 0.5, y + 0.5, z - 0.5],
+                [x + half, y - half, z - half],
+                [x + half, y - half, z + half],
+                [x + half, y + half, z + half],
+                [x + half, y + half, z - half],
             ],
         ),
         (
             IVec3::NEG_X,
             [
-                [x - 0.5, y - 0.5, z + 0.5],
-                [x - 0.5, y - 0.5, z - 0.5],
-                [x - 0.5, y + 0.5, z - 0.5],
-                [x - 0.5, y + 0.5, z + 0.5],
+                [x - half, y - half, z + half],
+                [x - half, y - half, z - half],
+                [x - half, y + half, z - half],
+                [x - half, y + half, z + half],
             ],
         ),
         (
             IVec3::Y,
             [
-                [x - 0.5, y + 0.5, z - 0.5],
-                [x + 0.5, y + 0.5, z - 0.5],
-                [x + 0.5, y + 0.5, z + 0.5],
-                [x - 0.5, y + 0.5, z + 0.5],
+                [x - half, y + half, z - half],
+                [x + half, y + half, z - half],
+                [x + half, y + half, z + half],
+                [x - half, y + half, z + half],
             ],
         ),
         (
             IVec3::NEG_Y,
             [
-                [x - 0.5, y - 0.5, z + 0.5],
-                [x + 0.5, y - 0.5, z + 0.5],
-                [x + 0.5, y - 0.5, z - 0.5],
-                [x - 0.5, y - 0.5, z - 0.5],
+                [x - half, y - half, z + half],
+                [x + half, y - half, z + half],
+                [x + half, y - half, z - half],
+                [x - half, y - half, z - half],
             ],
         ),
         (
             IVec3::Z,
             [
-                [x + 0.5, y - 0.5, z + 0.5],
-                [x - 0.5, y - 0.5, z + 0.5],
-                [x - 0.5, y + 0.5, z + 0.5],
-                [x + 0.5, y + 0.5, z + 0.5],
+                [x + half, y - half, z + half],
+                [x - half, y - half, z + half],
+                [x - half, y + half, z + half],
+                [x + half, y + half, z + half],
             ],
         ),
         (
             IVec3::NEG_Z,
             [
-                [x - 0.5, y - 0.5, z - 0.5],
-                [x + 0.5, y - 0.5, z - 0.5],
-                [x + 0.5, y + 0.5, z - 0.5],
-                [x - 0.5, y + 0.5, z - 0.5],
+                [x - half, y - half, z - half],
+                [x + half, y - half, z - half],
+                [x + half, y + half, z - half],
+                [x - half, y + half, z - half],
             ],
         ),
     ]
@@ -446,6 +1487,8 @@ fn block_color(block_type: BlockType) -> Color {
         BlockType::Grass => Color::srgb(0.3, 0.7, 0.25),
         BlockType::Dirt => Color::srgb(0.45, 0.3, 0.16),
         BlockType::Stone => Color::srgb(0.5, 0.5, 0.55),
+        BlockType::Bedrock => Color::srgb(0.18, 0.18, 0.2),
+        BlockType::Water => Color::srgba(0.2, 0.45, 0.85, 0.6),
     }
 }
 
@@ -459,155 +1502,785 @@ fn is_player_air_cell(position: IVec3, player_position: IVec3) -> bool {
     in_horizontal && in_vertical
 }
 
-fn lock_cursor_on_click(
-    mouse: Res<ButtonInput<MouseButton>>,
-    key: Res<ButtonInput<KeyCode>>,
-    mut windows: Query<&mut Window, With<PrimaryWindow>>,
-) {
-    let mut window = windows.single_mut();
-
-    if mouse.just_pressed(MouseButton::Left) {
-        window.cursor.grab_mode = CursorGrabMode::Locked;
-        window.cursor.visible = false;
-    }
+/// Whether a block at `cell` would intersect the player's body hanging below `eye`.
+fn block_overlaps_player(cell: IVec3, eye: Vec3) -> bool {
+    let body_min = Vec3::new(
+        eye.x - PLAYER_HALF_WIDTH,
+        eye.y - PLAYER_EYE_HEIGHT,
+        eye.z - PLAYER_HALF_WIDTH,
+    );
+    let body_max = Vec3::new(
+        eye.x + PLAYER_HALF_WIDTH,
+        eye.y + PLAYER_HEAD_CLEARANCE,
+        eye.z + PLAYER_HALF_WIDTH,
+    );
+    let block_min = cell.as_vec3() - Vec3::splat(0.5);
+    let block_max = cell.as_vec3() + Vec3::splat(0.5);
 
-    if key.just_pressed(KeyCode::Escape) {
-        window.cursor.grab_mode = CursorGrabMode::None;
-        window.cursor.visible = true;
-    }
+    body_min.cmplt(block_max).all() && body_max.cmpgt(block_min).all()
 }
 
-fn player_look(
-    mut mouse_motion: EventReader<MouseMotion>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    mut query: Query<(&mut Transform, &mut Player)>,
+fn spread_grass(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut world: ResMut<WorldBlocks>,
+    mut spread: ResMut<GrassSpread>,
+    render: Res<BlockRenderResources>,
 ) {
-    let window = windows.single();
-
-    if window.cursor.grab_mode != CursorGrabMode::Locked {
-        mouse_motion.clear();
+    if !spread.timer.tick(time.delta()).just_finished() || world.chunks.is_empty() {
         return;
     }
 
-    let delta = mouse_motion
-        .read()
-        .fold(Vec2::ZERO, |acc, evt| acc + evt.delta);
-    if delta == Vec2::ZERO {
-        return;
+    let chunk_keys: Vec<IVec2> = world.chunks.keys().copied().collect();
+    let mut changes = Vec::new();
+
+    for _ in 0..GRASS_SPREAD_SAMPLES {
+        let chunk = chunk_keys[spread.rng.next() as usize % chunk_keys.len()];
+        let blocks = &world.chunks[&chunk].blocks;
+        if blocks.is_empty() {
+            continue;
+        }
+        let position = blocks[spread.rng.next() as usize % blocks.len()];
+        let covered = is_opaque_at(&world.map, position + IVec3::Y);
+
+        match world.map.get(&position) {
+            Some(BlockType::Grass) if covered => changes.push((position, BlockType::Dirt)),
+            Some(BlockType::Dirt) if !covered && has_adjacent_grass(&world.map, position) => {
+                changes.push((position, BlockType::Grass))
+            }
+            _ => {}
+        }
     }
 
-    let (mut transform, mut player) = query.single_mut();
-    player.yaw -= delta.x * MOUSE_SENSITIVITY;
-    player.pitch -= delta.y * MOUSE_SENSITIVITY;
-    player.pitch = player.pitch.clamp(-1.54, 1.54);
+    let mut dirty_chunks = HashSet::new();
+    for (position, block_type) in changes {
+        world.map.insert(position, block_type);
+        dirty_chunks.insert(world_to_chunk(position));
+    }
 
-    transform.rotation = Quat::from_euler(EulerRot::YXZ, player.yaw, player.pitch, 0.0);
+    for chunk in dirty_chunks {
+        rebuild_chunk_mesh(&mut commands, &mut meshes, &mut world, &render, chunk);
+    }
 }
 
-fn player_movement(
-    time: Res<Time>,
+fn has_adjacent_grass(map: &HashMap<IVec3, BlockType>, position: IVec3) -> bool {
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                if dx == 0 && dz == 0 {
+                    continue;
+                }
+                let neighbor = position + IVec3::new(dx, dy, dz);
+                if matches!(map.get(&neighbor), Some(BlockType::Grass)) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn save_world(
     keyboard: Res<ButtonInput<KeyCode>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    mut query: Query<&mut Transform, With<Player>>,
+    world: Res<WorldBlocks>,
+    world_gen: Res<WorldGenerator>,
 ) {
-    let window = windows.single();
-    if window.cursor.grab_mode != CursorGrabMode::Locked {
+    if !keyboard.just_pressed(KeyCode::F5) {
         return;
     }
 
-    let mut transform = query.single_mut();
-
-    let forward = transform.forward();
-    let right = transform.right();
-
-    let mut direction = Vec3::ZERO;
-
-    if keyboard.pressed(KeyCode::KeyW) {
-        direction += *forward;
-    }
-    if keyboard.pressed(KeyCode::KeyS) {
-        direction -= *forward;
-    }
-    if keyboard.pressed(KeyCode::KeyA) {
-        direction -= *right;
-    }
-    if keyboard.pressed(KeyCode::KeyD) {
-        direction += *right;
-    }
-    if keyboard.pressed(KeyCode::Space) {
-        direction += Vec3::Y;
-    }
-    if keyboard.pressed(KeyCode::ShiftLeft) {
-        direction -= Vec3::Y;
-    }
+    let saved = SavedWorld {
+        seed: world_gen.seed,
+        edits: world
+            .edits
+            .iter()
+            .map(|(position, &edit)| (position.to_array(), edit))
+            .collect(),
+    };
 
-    if direction.length_squared() > 0.0 {
-        transform.translation += direction.normalize() * PLAYER_SPEED * time.delta_seconds();
+    let result = bincode::serialize(&saved)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| std::fs::write(SAVE_PATH, bytes).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Saved {} edited blocks to {SAVE_PATH}", saved.edits.len()),
+        Err(err) => warn!("Failed to save world to {SAVE_PATH}: {err}"),
     }
 }
 
-fn block_interaction(
-    mouse: Res<ButtonInput<MouseButton>>,
+fn load_world(
+    keyboard: Res<ButtonInput<KeyCode>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut world: ResMut<WorldBlocks>,
+    world_gen: Res<WorldGenerator>,
     render: Res<BlockRenderResources>,
-    camera: Query<&Transform, With<Player>>,
+    player: Query<&Transform, With<Player>>,
 ) {
-    if !mouse.just_pressed(MouseButton::Left) && !mouse.just_pressed(MouseButton::Right) {
+    if !keyboard.just_pressed(KeyCode::F9) {
         return;
     }
 
-    let camera = camera.single();
-    let origin = camera.translation;
-    let direction = *camera.forward();
-
-    let mut previous_cell: Option<IVec3> = None;
-    let mut hit_cell: Option<IVec3> = None;
+    let bytes = match std::fs::read(SAVE_PATH) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!("Failed to read {SAVE_PATH}: {err}");
+            return;
+        }
+    };
+    let saved: SavedWorld = match bincode::deserialize(&bytes) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Failed to parse {SAVE_PATH}: {err}");
+            return;
+        }
+    };
+    if saved.seed != world_gen.seed {
+        warn!(
+            "Not loading {SAVE_PATH}: it was saved with seed {} but the world uses seed {}",
+            saved.seed, world_gen.seed
+        );
+        return;
+    }
 
-    let step = 0.1;
-    let steps = (REACH_DISTANCE / step) as i32;
+    let edits: HashMap<IVec3, Option<BlockType>> = saved
+        .edits
+        .into_iter()
+        .map(|(position, edit)| (IVec3::from_array(position), edit))
+        .collect();
 
-    for i in 0..=steps {
-        let point = origin + direction * (i as f32 * step);
-        let cell = point.round().as_ivec3();
+    // Chunks touched by either the current or the loaded edits must be regenerated.
+    let affected_chunks: HashSet<IVec2> = world
+        .edits
+        .keys()
+        .chain(edits.keys())
+        .map(|&position| world_to_chunk(position))
+        .filter(|chunk| world_gen.generated_chunks.contains(chunk))
+        .collect();
+    world.edits = edits;
 
-        if world.map.contains_key(&cell) {
-            hit_cell = Some(cell);
-            break;
+    let player_pos = player.single().translation.round().as_ivec3();
+    for chunk in affected_chunks {
+        let WorldBlocks { map, chunks, .. } = &mut *world;
+        if let Some(chunk_data) = chunks.get_mut(&chunk) {
+            for position in std::mem::take(&mut chunk_data.blocks) {
+                map.remove(&position);
+            }
         }
 
-        previous_cell = Some(cell);
+        let blocks = generate_chunk(&world_gen.noise, chunk, player_pos);
+        insert_generated_chunk(&mut world, chunk, blocks);
+        rebuild_chunk_and_neighbors(&mut commands, &mut meshes, &mut world, &render, chunk);
     }
 
-    let mut dirty_chunks = HashSet::new();
+    info!(
+        "Loaded {} edited blocks from {SAVE_PATH}",
+        world.edits.len()
+    );
+}
+
+fn cycle_difficulty(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<DifficultySettings>) {
+    if !keyboard.just_pressed(KeyCode::F7) {
+        return;
+    }
+
+    let next = match settings.preset {
+        Difficulty::Peaceful => Difficulty::Normal,
+        Difficulty::Normal => Difficulty::Hard,
+        Difficulty::Hard => Difficulty::Peaceful,
+    };
+    *settings = DifficultySettings::from_preset(next);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_mobs(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut spawner: ResMut<MobSpawner>,
+    settings: Res<DifficultySettings>,
+    world: Res<WorldBlocks>,
+    render: Res<MobRenderResources>,
+    player: Query<&Transform, With<Player>>,
+    mobs: Query<(Entity, &Transform), With<Mob>>,
+) {
+    if settings.is_changed() {
+        spawner
+            .timer
+            .set_duration(Duration::from_secs_f32(settings.spawn_interval.max(0.05)));
+    }
+
+    let mut alive = 0;
+    for (entity, transform) in &mobs {
+        // Mobs left behind in unloaded terrain have no ground and would hold spawn slots forever.
+        let chunk = world_to_chunk(transform.translation.round().as_ivec3());
+        let stranded = !world.chunks.contains_key(&chunk);
+        // Lowering the cap (e.g. switching to peaceful) removes the surplus immediately.
+        let surplus = settings.is_changed() && alive >= settings.max_mobs;
+        if stranded || surplus {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            alive += 1;
+        }
+    }
+
+    if !spawner.timer.tick(time.delta()).just_finished() || alive >= settings.max_mobs {
+        return;
+    }
+
+    let player_pos = player.single().translation;
+    let angle = spawner.rng.next_f32() * std::f32::consts::TAU;
+    let distance = MOB_SPAWN_MIN_DISTANCE
+        + spawner.rng.next_f32() * (MOB_SPAWN_MAX_DISTANCE - MOB_SPAWN_MIN_DISTANCE);
+    let x = (player_pos.x + angle.cos() * distance).round() as i32;
+    let z = (player_pos.z + angle.sin() * distance).round() as i32;
+
+    // Columns that aren't streamed in yet have no ground to stand on.
+    let Some(ground) = ground_height(&world.map, x, z, MAX_HEIGHT + 1) else {
+        return;
+    };
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                x as f32,
+                ground as f32 + 0.5,
+                z as f32,
+            )),
+            Mob::new(MOB_MAX_HEALTH * settings.health_multiplier),
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: render.mesh.clone(),
+                material: render.material.clone(),
+                transform: Transform::from_xyz(0.0, MOB_HEIGHT * 0.5, 0.0),
+                ..default()
+            });
+        });
+}
+
+/// Walks mobs towards the player while they can see them, otherwise lets them wander. Feet
+/// snap to the ground and step up at most one block at a time.
+fn move_mobs(
+    time: Res<Time>,
+    world: Res<WorldBlocks>,
+    settings: Res<DifficultySettings>,
+    mut spawner: ResMut<MobSpawner>,
+    player: Query<&Transform, (With<Player>, Without<Mob>)>,
+    mut mobs: Query<(&mut Transform, &mut Mob)>,
+) {
+    let player_pos = player.single().translation;
+    let dt = time.delta_seconds();
+
+    for (mut transform, mut mob) in &mut mobs {
+        let position = transform.translation;
+        let sees_player = has_line_of_sight(
+            &world.map,
+            position + Vec3::Y * MOB_EYE_HEIGHT,
+            player_pos,
+            settings.sight_range,
+        );
+
+        mob.unstuck_timer = (mob.unstuck_timer - dt).max(0.0);
+        let direction = if sees_player && mob.unstuck_timer <= 0.0 {
+            let to_player = Vec3::new(player_pos.x - position.x, 0.0, player_pos.z - position.z);
+            if to_player.length_squared() < 1.5 * 1.5 {
+                continue;
+            }
+            to_player.normalize()
+        } else {
+            mob.wander_timer -= dt;
+            if mob.wander_timer <= 0.0 {
+                let angle = spawner.rng.next_f32() * std::f32::consts::TAU;
+                mob.wander_direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+                mob.wander_timer = MOB_WANDER_INTERVAL;
+            }
+            mob.wander_direction
+        };
+
+        let speed = MOB_SPEED * settings.speed_multiplier;
+        let next = position + direction * speed * dt;
+        let feet = (position.y - 0.5).round() as i32;
+        let column = next.round().as_ivec3();
+
+        let ground = ground_height(&world.map, column.x, column.z, feet + 1);
+        let walkable = ground.is_some_and(|ground| {
+            (1..=MOB_HEIGHT.ceil() as i32)
+                .all(|dy| !is_opaque_at(&world.map, IVec3::new(column.x, ground + dy, column.z)))
+        });
+        let Some(ground) = ground.filter(|_| walkable) else {
+            // Pick a new heading next frame instead of walking into the wall, and if that keeps
+            // failing, nudge sideways for a while even when the player is in sight.
+            mob.wander_timer = 0.0;
+            mob.stuck_time += dt;
+            if mob.stuck_time >= MOB_STUCK_TIME {
+                mob.stuck_time = 0.0;
+                mob.unstuck_timer = MOB_UNSTUCK_DURATION;
+            }
+            continue;
+        };
+        mob.stuck_time = 0.0;
+
+        transform.translation = Vec3::new(next.x, ground as f32 + 0.5, next.z);
+        transform.look_to(direction, Vec3::Y);
+    }
+}
+
+fn has_line_of_sight(
+    map: &HashMap<IVec3, BlockType>,
+    from: Vec3,
+    to: Vec3,
+    max_distance: f32,
+) -> bool {
+    let distance = from.distance(to);
+    distance <= max_distance && raycast_block(map, from, to - from, distance).is_none()
+}
+
+fn attack_mobs(
+    mut commands: Commands,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    world: Res<WorldBlocks>,
+    settings: Res<InteractionSettings>,
+    camera: Query<&Transform, With<Player>>,
+    mut mobs: Query<(Entity, &Transform, &mut Mob), Without<Player>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let camera = camera.single();
+    let origin = camera.translation;
+    let direction = *camera.forward();
+    let block_distance = raycast_block(&world.map, origin, direction, settings.reach)
+        .map_or(settings.reach, |hit| hit.distance);
+
+    let half_extents = Vec3::new(MOB_HALF_WIDTH, MOB_HEIGHT * 0.5, MOB_HALF_WIDTH);
+    let target = mobs
+        .iter_mut()
+        .filter_map(|(entity, transform, mob)| {
+            let center = transform.translation + Vec3::Y * MOB_HEIGHT * 0.5;
+            ray_aabb(
+                origin,
+                direction,
+                center - half_extents,
+                center + half_extents,
+            )
+            .filter(|&distance| distance <= block_distance)
+            .map(|distance| (distance, entity, mob))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+
+    let Some((_, entity, mut mob)) = target else {
+        return;
+    };
+
+    // The click hit a mob, so it must not also break the block behind it.
+    mouse.clear_just_pressed(MouseButton::Left);
+    mob.health -= MOB_HIT_DAMAGE;
+    if mob.health <= 0.0 {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Slab test returning the entry distance of a ray into an axis-aligned box.
+fn ray_aabb(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let inv = direction.recip();
+    let t1 = (min - origin) * inv;
+    let t2 = (max - origin) * inv;
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+
+    (t_near <= t_far && t_far >= 0.0).then_some(t_near.max(0.0))
+}
+
+/// Returns the y of the highest opaque block in the column at or below `from_y`.
+fn ground_height(map: &HashMap<IVec3, BlockType>, x: i32, z: i32, from_y: i32) -> Option<i32> {
+    (BEDROCK_LEVEL..=from_y)
+        .rev()
+        .find(|&y| is_opaque_at(map, IVec3::new(x, y, z)))
+}
+
+/// Throws away every loaded chunk, edit and mob and starts streaming a world with a new seed.
+fn reset_world(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+    mut world: ResMut<WorldBlocks>,
+    mut world_gen: ResMut<WorldGenerator>,
+    mut box_tool: ResMut<BoxTool>,
+    chunk_entities: Query<Entity, Or<(With<BlockChunk>, With<ChunkGenerationTask>, With<Mob>)>>,
+    mut player: Query<&mut Transform, With<Player>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F8) {
+        return;
+    }
+
+    for entity in &chunk_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    *world = WorldBlocks::default();
+    box_tool.first_corner = None;
+    let seed = world_gen.seed.wrapping_mul(0x9e37_79b9).wrapping_add(1);
+    *world_gen = WorldGenerator {
+        seed,
+        noise: Perlin::new(seed),
+        generated_chunks: HashSet::new(),
+        pending_chunks: HashMap::new(),
+    };
+
+    // The new terrain isn't generated yet, so drop the player above the tallest possible column;
+    // generation keeps the cells around them clear.
+    player.single_mut().translation.y = (MAX_HEIGHT + 4) as f32;
+    info!("Regenerating world with seed {seed}");
+}
+
+/// Advances the day/night cycle and drives the sky colour, ambient light and sun from it.
+fn update_time_of_day(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    player: Query<&Transform, (With<Player>, Without<Sun>)>,
+    mut sun: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+) {
+    let mut hour = time_of_day.hour + time.delta_seconds() / DAY_LENGTH_SECONDS * 24.0;
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        hour += TIME_SKIP_HOURS;
+    }
+    time_of_day.hour = hour.rem_euclid(24.0);
+
+    let daylight = time_of_day.daylight();
+    let sky = NIGHT_SKY_COLOR.lerp(DAY_SKY_COLOR, daylight);
+    clear_color.0 = Color::srgb(sky.x, sky.y, sky.z);
+    ambient.brightness =
+        NIGHT_AMBIENT_BRIGHTNESS + (DAY_AMBIENT_BRIGHTNESS - NIGHT_AMBIENT_BRIGHTNESS) * daylight;
+
+    // Keep the sun centred on the player so the shadow cascades cover the loaded terrain.
+    let focus = player.single().translation;
+    let (mut light, mut transform) = sun.single_mut();
+    light.illuminance = SUN_ILLUMINANCE * daylight;
+    *transform = Transform::from_translation(focus + time_of_day.sun_direction() * SUN_DISTANCE)
+        .looking_at(focus, Vec3::Y);
+}
+
+fn toggle_wireframe(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    render_device: Res<RenderDevice>,
+    mut config: ResMut<WireframeConfig>,
+) {
+    // Line polygon mode is an optional adapter feature; leave the toggle inert without it.
+    let supported = render_device
+        .features()
+        .contains(WgpuFeatures::POLYGON_MODE_LINE);
+    if keyboard.just_pressed(KeyCode::F4) && supported {
+        config.global = !config.global;
+    }
+}
+
+/// Pauses or rescales virtual time, which drives world simulation (mobs, grass). Looking,
+/// flying and editing run on real time so the world can be inspected while paused.
+fn simulation_controls(keyboard: Res<ButtonInput<KeyCode>>, mut time: ResMut<Time<Virtual>>) {
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+
+    let speed = time.relative_speed();
+    if keyboard.just_pressed(KeyCode::BracketRight) {
+        time.set_relative_speed((speed * 2.0).min(MAX_SIMULATION_SPEED));
+    }
+    if keyboard.just_pressed(KeyCode::BracketLeft) {
+        time.set_relative_speed((speed * 0.5).max(MIN_SIMULATION_SPEED));
+    }
+}
+
+fn lock_cursor_on_click(
+    mouse: Res<ButtonInput<MouseButton>>,
+    key: Res<ButtonInput<KeyCode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let mut window = windows.single_mut();
 
     if mouse.just_pressed(MouseButton::Left) {
+        window.cursor.grab_mode = CursorGrabMode::Locked;
+        window.cursor.visible = false;
+    }
+
+    if key.just_pressed(KeyCode::Escape) {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+fn player_look(
+    time: Res<Time<Real>>,
+    settings: Res<LookSettings>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(&mut Transform, &mut Player, &Projection)>,
+) {
+    let window = windows.single();
+
+    if window.cursor.grab_mode != CursorGrabMode::Locked {
+        mouse_motion.clear();
+        return;
+    }
+
+    let delta = mouse_motion
+        .read()
+        .fold(Vec2::ZERO, |acc, evt| acc + evt.delta);
+
+    let (mut transform, mut player, projection) = query.single_mut();
+    // Slow the look down while zoomed so aiming stays precise.
+    let zoom_scale = match projection {
+        Projection::Perspective(perspective) => {
+            perspective.fov / clamp_fov(settings.fov_degrees).to_radians()
+        }
+        _ => 1.0,
+    };
+    let invert = if settings.invert_y { -1.0 } else { 1.0 };
+    player.target_yaw -= delta.x * settings.sensitivity_x * zoom_scale;
+    player.target_pitch -= delta.y * settings.sensitivity_y * zoom_scale * invert;
+    player.target_pitch = player.target_pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+    if player.yaw == player.target_yaw && player.pitch == player.target_pitch {
+        return;
+    }
+
+    if settings.smoothing > 0.0 {
+        let t = 1.0 - (-time.delta_seconds() / settings.smoothing).exp();
+        player.yaw = player.yaw.lerp(player.target_yaw, t);
+        player.pitch = player.pitch.lerp(player.target_pitch, t);
+    } else {
+        player.yaw = player.target_yaw;
+        player.pitch = player.target_pitch;
+    }
+
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, player.yaw, player.pitch, 0.0);
+}
+
+fn update_fov(
+    time: Res<Time<Real>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<LookSettings>,
+    mut query: Query<&mut Projection, With<Player>>,
+) {
+    let mut projection = query.single_mut();
+    let Projection::Perspective(perspective) = projection.as_mut() else {
+        return;
+    };
+
+    let target_degrees = if keyboard.pressed(KeyCode::KeyZ) {
+        settings.zoom_fov_degrees
+    } else {
+        settings.fov_degrees
+    };
+    let target = clamp_fov(target_degrees).to_radians();
+
+    let t = 1.0 - (-ZOOM_SPEED * time.delta_seconds()).exp();
+    perspective.fov = perspective.fov.lerp(target, t);
+}
+
+fn clamp_fov(degrees: f32) -> f32 {
+    degrees.clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES)
+}
+
+fn player_movement(
+    time: Res<Time<Real>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<MovementSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<&mut Transform, With<Player>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        settings.planar = !settings.planar;
+    }
+
+    let window = windows.single();
+    if window.cursor.grab_mode != CursorGrabMode::Locked {
+        return;
+    }
+
+    let mut transform = query.single_mut();
+
+    let look = *transform.forward();
+    let forward = if settings.planar {
+        Vec3::new(look.x, 0.0, look.z).normalize_or_zero()
+    } else {
+        look
+    };
+    let right = transform.right();
+
+    let mut direction = Vec3::ZERO;
+
+    if keyboard.pressed(KeyCode::KeyW) {
+        direction += forward;
+    }
+    if keyboard.pressed(KeyCode::KeyS) {
+        direction -= forward;
+    }
+    if keyboard.pressed(KeyCode::KeyA) {
+        direction -= *right;
+    }
+    if keyboard.pressed(KeyCode::KeyD) {
+        direction += *right;
+    }
+    if keyboard.pressed(KeyCode::Space) {
+        direction += Vec3::Y;
+    }
+    if keyboard.pressed(KeyCode::ShiftLeft) {
+        direction -= Vec3::Y;
+    }
+
+    if direction.length_squared() > 0.0 {
+        transform.translation += direction.normalize() * PLAYER_SPEED * time.delta_seconds();
+    }
+
+    // Blocks are centred on integer coordinates, so the ragged bedrock layer at
+    // BEDROCK_LEVEL + 1 reaches up to BEDROCK_LEVEL + 1.5; keep the eye clear of it.
+    let floor = BEDROCK_LEVEL as f32 + 2.0;
+    transform.translation.y = transform.translation.y.max(floor);
+}
+
+fn select_hotbar_slot(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    const SLOT_KEYS: [KeyCode; 3] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+
+    for (index, key) in SLOT_KEYS.iter().enumerate() {
+        if keyboard.just_pressed(*key) {
+            hotbar.selected = index;
+        }
+    }
+
+    let scroll: f32 = mouse_wheel.read().map(|evt| evt.y).sum();
+    let slots = PLACEABLE_BLOCKS.len() as i32;
+    if scroll > 0.0 {
+        hotbar.selected = (hotbar.selected as i32 - 1).rem_euclid(slots) as usize;
+    } else if scroll < 0.0 {
+        hotbar.selected = (hotbar.selected as i32 + 1).rem_euclid(slots) as usize;
+    }
+}
+
+fn toggle_creative_mode(keyboard: Res<ButtonInput<KeyCode>>, mut inventory: ResMut<Inventory>) {
+    if keyboard.just_pressed(KeyCode::KeyC) {
+        inventory.creative = !inventory.creative;
+    }
+}
+
+fn update_hotbar(hotbar: Res<Hotbar>, mut slots: Query<(&HotbarSlot, &mut BorderColor)>) {
+    if !hotbar.is_changed() {
+        return;
+    }
+
+    for (slot, mut border) in &mut slots {
+        *border = if slot.0 == hotbar.selected {
+            Color::WHITE.into()
+        } else {
+            Color::BLACK.with_alpha(0.5).into()
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn block_interaction(
+    time: Res<Time<Real>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut world: ResMut<WorldBlocks>,
+    render: Res<BlockRenderResources>,
+    hotbar: Res<Hotbar>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut inventory: ResMut<Inventory>,
+    mut box_tool: ResMut<BoxTool>,
+    settings: Res<InteractionSettings>,
+    mut cooldowns: ResMut<InteractionCooldowns>,
+    camera: Query<&Transform, With<Player>>,
+) {
+    let cancel_box = keyboard.just_pressed(KeyCode::Escape) || !inventory.creative;
+    if cancel_box && box_tool.first_corner.is_some() {
+        box_tool.first_corner = None;
+    }
+
+    let now = time.elapsed_seconds();
+    let break_pressed = mouse.just_pressed(MouseButton::Left)
+        && now - cooldowns.last_break >= settings.break_cooldown;
+    let place_pressed = mouse.just_pressed(MouseButton::Right)
+        && now - cooldowns.last_place >= settings.place_cooldown;
+    if !break_pressed && !place_pressed {
+        return;
+    }
+    if break_pressed {
+        cooldowns.last_break = now;
+    }
+    if place_pressed {
+        cooldowns.last_place = now;
+    }
+
+    let camera = camera.single();
+    let hit = raycast_block(
+        &world.map,
+        camera.translation,
+        *camera.forward(),
+        settings.reach,
+    );
+    let hit_cell = hit.map(|hit| hit.cell);
+
+    let mut dirty_chunks = HashSet::new();
+    let fill_modifier =
+        keyboard.pressed(KeyCode::ControlLeft) || keyboard.pressed(KeyCode::ControlRight);
+    let box_modifier = keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight);
+
+    if fill_modifier && inventory.creative && place_pressed {
+        if let Some(cell) = hit_cell {
+            dirty_chunks = world.flood_fill(cell, hotbar.selected_block());
+        }
+    } else if box_modifier && inventory.creative {
         if let Some(cell) = hit_cell {
-            if world.map.remove(&cell).is_some() {
-                let chunk = world_to_chunk(cell);
-                if let Some(chunk_data) = world.chunks.get_mut(&chunk) {
-                    chunk_data.blocks.retain(|&p| p != cell);
+            match box_tool.first_corner.take() {
+                None => box_tool.first_corner = Some(cell),
+                Some(first) => {
+                    let fill = place_pressed.then(|| hotbar.selected_block());
+                    dirty_chunks = world.fill_box(first, cell, fill);
+                }
+            }
+        }
+    } else {
+        if break_pressed {
+            if let Some(cell) = hit_cell {
+                let breakable = !matches!(world.map.get(&cell), Some(BlockType::Bedrock));
+                let removed = if breakable {
+                    world.remove_block(cell)
+                } else {
+                    None
+                };
+                if let Some(block_type) = removed {
+                    inventory.add(block_type);
+                    let chunk = world_to_chunk(cell);
+                    dirty_chunks.insert(chunk);
+                    dirty_chunks.extend(chunk_neighbors_inclusive(chunk));
                 }
-                dirty_chunks.insert(chunk);
-                dirty_chunks.extend(chunk_neighbors_inclusive(chunk));
             }
         }
-    }
 
-    if mouse.just_pressed(MouseButton::Right) && hit_cell.is_some() {
-        if let Some(place_pos) = previous_cell {
-            if !world.map.contains_key(&place_pos) {
-                world.map.insert(place_pos, BlockType::Grass);
-                let chunk = world_to_chunk(place_pos);
-                world
-                    .chunks
-                    .entry(chunk)
-                    .or_default()
-                    .blocks
-                    .push(place_pos);
-                dirty_chunks.insert(chunk);
-                dirty_chunks.extend(chunk_neighbors_inclusive(chunk));
+        if place_pressed {
+            if let Some(place_pos) = hit.map(|hit| hit.cell + hit.normal) {
+                let block_type = hotbar.selected_block();
+                let replaceable = !is_opaque_at(&world.map, place_pos)
+                    && world.chunks.contains_key(&world_to_chunk(place_pos))
+                    && !block_overlaps_player(place_pos, camera.translation);
+                if replaceable && inventory.take(block_type) {
+                    world.insert_block(place_pos, block_type);
+                    let chunk = world_to_chunk(place_pos);
+                    dirty_chunks.insert(chunk);
+                    dirty_chunks.extend(chunk_neighbors_inclusive(chunk));
+                }
             }
         }
     }
@@ -616,3 +2289,536 @@ fn block_interaction(
         rebuild_chunk_mesh(&mut commands, &mut meshes, &mut world, &render, chunk);
     }
 }
+
+#[derive(Clone, Copy)]
+struct RayHit {
+    cell: IVec3,
+    /// Distance along the ray to where it enters `cell`.
+    distance: f32,
+    /// Outward normal of the face the ray entered through; zero if the ray starts inside `cell`.
+    normal: IVec3,
+}
+
+/// Walks the voxel grid along the ray (Amanatides & Woo DDA) and returns the first solid cell
+/// within `max_distance`, passing through water. Blocks are unit cubes centred on their
+/// integer coordinates.
+fn raycast_block(
+    map: &HashMap<IVec3, BlockType>,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<RayHit> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return None;
+    }
+
+    let start = origin + Vec3::splat(0.5);
+    let mut cell = start.floor().as_ivec3();
+    let mut step = IVec3::ZERO;
+    let mut t_max = Vec3::splat(f32::INFINITY);
+    let t_delta = direction.abs().recip();
+    for axis in 0..3 {
+        if direction[axis] > 0.0 {
+            step[axis] = 1;
+            t_max[axis] = (cell[axis] as f32 + 1.0 - start[axis]) * t_delta[axis];
+        } else if direction[axis] < 0.0 {
+            step[axis] = -1;
+            t_max[axis] = (start[axis] - cell[axis] as f32) * t_delta[axis];
+        }
+    }
+    let mut normal = IVec3::ZERO;
+    let mut distance = 0.0;
+
+    loop {
+        if is_opaque_at(map, cell) {
+            return Some(RayHit {
+                cell,
+                distance,
+                normal,
+            });
+        }
+
+        let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+            0
+        } else if t_max.y < t_max.z {
+            1
+        } else {
+            2
+        };
+        if t_max[axis] > max_distance {
+            return None;
+        }
+
+        distance = t_max[axis];
+        cell[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+        normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+    }
+}
+
+fn draw_block_highlight(
+    world: Res<WorldBlocks>,
+    settings: Res<InteractionSettings>,
+    camera: Query<&Transform, With<Player>>,
+    mut gizmos: Gizmos,
+) {
+    let camera = camera.single();
+    let hit = raycast_block(
+        &world.map,
+        camera.translation,
+        *camera.forward(),
+        settings.reach,
+    );
+    if let Some(hit) = hit {
+        gizmos.cuboid(
+            Transform::from_translation(hit.cell.as_vec3()).with_scale(Vec3::splat(1.01)),
+            Color::BLACK,
+        );
+    }
+}
+
+fn draw_box_tool_preview(
+    box_tool: Res<BoxTool>,
+    world: Res<WorldBlocks>,
+    settings: Res<InteractionSettings>,
+    camera: Query<&Transform, With<Player>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(first) = box_tool.first_corner else {
+        return;
+    };
+
+    let camera = camera.single();
+    let hit = raycast_block(
+        &world.map,
+        camera.translation,
+        *camera.forward(),
+        settings.reach,
+    );
+    let second = hit.map_or(first, |hit| hit.cell);
+    let min = first.min(second);
+    let max = first.max(second);
+
+    let center = (min + max).as_vec3() * 0.5;
+    let size = (max - min + IVec3::ONE).as_vec3() + Vec3::splat(0.02);
+    let color = if box_volume(min, max) > BOX_TOOL_MAX_VOLUME {
+        Color::srgb(0.9, 0.2, 0.2)
+    } else {
+        Color::WHITE
+    };
+    gizmos.cuboid(Transform::from_translation(center).with_scale(size), color);
+}
+
+fn box_volume(min: IVec3, max: IVec3) -> usize {
+    let extent = max - min + IVec3::ONE;
+    (extent.x * extent.y * extent.z) as usize
+}
+
+fn update_crosshair(
+    mut commands: Commands,
+    settings: Res<CrosshairSettings>,
+    query: Query<Entity, With<Crosshair>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let size = settings.size.max(1.0);
+    let thickness = settings.thickness.clamp(1.0, size);
+    let color = settings.color;
+    let root = query.single();
+
+    commands.entity(root).despawn_descendants();
+    commands
+        .entity(root)
+        .with_children(|parent| match settings.style {
+            CrosshairStyle::Dot => {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                });
+            }
+            CrosshairStyle::Cross => {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(size),
+                            height: Val::Px(size),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|cross| {
+                        let offset = (size - thickness) * 0.5;
+                        for (width, height, left, top) in [
+                            (size, thickness, 0.0, offset),
+                            (thickness, size, offset, 0.0),
+                        ] {
+                            cross.spawn(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    width: Val::Px(width),
+                                    height: Val::Px(height),
+                                    left: Val::Px(left),
+                                    top: Val::Px(top),
+                                    ..default()
+                                },
+                                background_color: color.into(),
+                                ..default()
+                            });
+                        }
+                    });
+            }
+            CrosshairStyle::Circle => {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        border: UiRect::all(Val::Px(thickness)),
+                        ..default()
+                    },
+                    border_color: color.into(),
+                    border_radius: BorderRadius::MAX,
+                    ..default()
+                });
+            }
+        });
+}
+
+fn toggle_vsync(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F6) {
+        return;
+    }
+
+    // The Auto modes fall back to whatever the surface supports (Immediate, then Mailbox,
+    // then Fifo without vsync), so switching never requests an unavailable mode.
+    let mut window = windows.single_mut();
+    window.present_mode = match window.present_mode {
+        PresentMode::AutoNoVsync => PresentMode::AutoVsync,
+        _ => PresentMode::AutoNoVsync,
+    };
+}
+
+fn update_fps_counter(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut counter: ResMut<FpsCounter>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<&mut Text, With<FpsText>>,
+) {
+    if keyboard.just_pressed(KeyCode::F2) {
+        counter.show_frame_time = !counter.show_frame_time;
+    }
+
+    let vsync = if windows.single().present_mode == PresentMode::AutoNoVsync {
+        "no vsync"
+    } else {
+        "vsync"
+    };
+
+    let mut text = query.single_mut();
+    let rate = if counter.show_frame_time {
+        diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+            .and_then(|frame_time| frame_time.smoothed())
+            .map(|ms| format!("{ms:.1} ms"))
+            .unwrap_or_default()
+    } else {
+        diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .map(|fps| format!("{fps:.0} fps"))
+            .unwrap_or_default()
+    };
+    text.sections[0].value = format!("{rate} ({vsync})");
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_debug_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    world: Res<WorldBlocks>,
+    world_gen: Res<WorldGenerator>,
+    time: Res<Time<Virtual>>,
+    time_of_day: Res<TimeOfDay>,
+    mut overlay: ResMut<DebugOverlay>,
+    player: Query<&Transform, With<Player>>,
+    meshes: Res<Assets<Mesh>>,
+    chunk_meshes: Query<&Handle<Mesh>, With<BlockChunk>>,
+    mut query: Query<(&mut Text, &mut Visibility), With<DebugText>>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+
+    let (mut text, mut visibility) = query.single_mut();
+    if !overlay.visible {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    let transform = player.single();
+    let position = transform.translation;
+    let block = position.round().as_ivec3();
+    let chunk = world_to_chunk(block);
+
+    let mut mesh_count = 0;
+    let mut vertex_count = 0;
+    let mut triangle_count = 0;
+    let mut mesh_bytes = 0;
+    for mesh in chunk_meshes.iter().filter_map(|handle| meshes.get(handle)) {
+        let vertices = mesh.count_vertices();
+        mesh_count += 1;
+        vertex_count += vertices;
+        triangle_count += mesh.indices().map_or(0, |indices| indices.len() / 3);
+        mesh_bytes += vertices * mesh.get_vertex_size() as usize
+            + mesh.indices().map_or(0, |indices| indices.len() * 4);
+    }
+
+    text.sections[0].value = format!(
+        "XYZ: {:.2} / {:.2} / {:.2}\nBlock: {} {} {}\nChunk: {} {}\nFacing: {}\nLoaded chunks: {}\nSeed: {}\nSimulation: {}\nTime: {:02}:{:02}\nChunk meshes: {} ({} vertices, {} triangles, {:.1} MiB)",
+        position.x,
+        position.y,
+        position.z,
+        block.x,
+        block.y,
+        block.z,
+        chunk.x,
+        chunk.y,
+        facing_label(*transform.forward()),
+        world.chunks.len(),
+        world_gen.seed,
+        if time.is_paused() {
+            "paused".to_string()
+        } else {
+            format!("x{:.2}", time.relative_speed())
+        },
+        time_of_day.hour as u32,
+        (time_of_day.hour.fract() * 60.0) as u32,
+        mesh_count,
+        vertex_count,
+        triangle_count,
+        mesh_bytes as f32 / (1024.0 * 1024.0),
+    );
+}
+
+/// Colours each minimap cell with the topmost block of its column, plus markers for the
+/// player and the direction they face. Refreshed on a timer since it scans many columns.
+fn update_minimap(
+    time: Res<Time<Real>>,
+    world: Res<WorldBlocks>,
+    mut minimap: ResMut<Minimap>,
+    player: Query<&Transform, With<Player>>,
+    mut cells: Query<(&MinimapCell, &mut BackgroundColor)>,
+) {
+    if !minimap.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let transform = player.single();
+    let center = transform.translation.round().as_ivec3();
+    let top = center.y.max(MAX_HEIGHT);
+    let forward = transform.forward();
+    let facing = Vec2::new(forward.x, forward.z)
+        .normalize_or_zero()
+        .round()
+        .as_ivec2();
+
+    for (cell, mut background) in &mut cells {
+        let x = center.x + cell.0.x * MINIMAP_BLOCKS_PER_CELL;
+        let z = center.z + cell.0.y * MINIMAP_BLOCKS_PER_CELL;
+        let surface = (BEDROCK_LEVEL..=top)
+            .rev()
+            .find_map(|y| world.map.get(&IVec3::new(x, y, z)).copied());
+
+        background.0 = if cell.0 == IVec2::ZERO {
+            Color::WHITE
+        } else if facing != IVec2::ZERO && cell.0 == facing {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            surface.map_or(Color::NONE, block_color)
+        };
+    }
+}
+
+fn facing_label(forward: Vec3) -> &'static str {
+    if forward.x.abs() > forward.z.abs() {
+        if forward.x > 0.0 {
+            "east (+X)"
+        } else {
+            "west (-X)"
+        }
+    } else if forward.z > 0.0 {
+        "south (+Z)"
+    } else {
+        "north (-Z)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::world::CommandQueue;
+
+    use super::*;
+
+    fn world_with_chunks(chunks: &[IVec2]) -> WorldBlocks {
+        let mut world = WorldBlocks::default();
+        for &chunk in chunks {
+            world.chunks.insert(chunk, ChunkData::default());
+        }
+        world
+    }
+
+    fn unload(world: &mut WorldBlocks, chunk: IVec2) {
+        let mut world_gen = WorldGenerator {
+            seed: WORLD_SEED,
+            noise: Perlin::new(WORLD_SEED),
+            generated_chunks: world.chunks.keys().copied().collect(),
+            pending_chunks: HashMap::new(),
+        };
+        let mut queue = CommandQueue::default();
+        let ecs = World::new();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        unload_chunk(&mut commands, world, &mut world_gen, chunk);
+        assert!(!world_gen.generated_chunks.contains(&chunk));
+    }
+
+    #[test]
+    fn repeated_place_and_break_keeps_chunk_blocks_unique() {
+        let mut world = world_with_chunks(&[IVec2::ZERO]);
+        let cell = IVec3::new(3, 5, 7);
+
+        for _ in 0..10 {
+            assert!(world.insert_block(cell, BlockType::Stone));
+            assert!(world.insert_block(cell, BlockType::Dirt));
+            let blocks = &world.chunks[&IVec2::ZERO].blocks;
+            assert_eq!(blocks.iter().filter(|&&p| p == cell).count(), 1);
+
+            assert_eq!(world.remove_block(cell), Some(BlockType::Dirt));
+            assert!(!world.chunks[&IVec2::ZERO].blocks.contains(&cell));
+        }
+    }
+
+    #[test]
+    fn border_placement_never_leaves_orphaned_chunk_data() {
+        let mut world = world_with_chunks(&[IVec2::ZERO]);
+        let inside = IVec3::new(CHUNK_SIZE - 1, 4, 0);
+        let outside = IVec3::new(CHUNK_SIZE, 4, 0);
+
+        assert!(!world.insert_block(outside, BlockType::Stone));
+        assert!(!world.map.contains_key(&outside));
+        assert!(!world.chunks.contains_key(&IVec2::new(1, 0)));
+
+        assert!(world.insert_block(inside, BlockType::Stone));
+        assert_eq!(world.map.get(&inside), Some(&BlockType::Stone));
+
+        unload(&mut world, IVec2::ZERO);
+        assert!(world.map.is_empty());
+        assert!(world.chunks.is_empty());
+    }
+
+    #[test]
+    fn shared_chunk_boundary_has_no_faces() {
+        let mut world = world_with_chunks(&[IVec2::ZERO, IVec2::X]);
+        for x in 0..CHUNK_SIZE * 2 {
+            for z in 0..CHUNK_SIZE {
+                assert!(world.insert_block(IVec3::new(x, 0, z), BlockType::Stone));
+            }
+        }
+
+        let boundary = CHUNK_SIZE as f32 - 0.5;
+        for chunk in [IVec2::ZERO, IVec2::X] {
+            let mesh = build_chunk_mesh(&world.map, &world.chunks[&chunk].blocks, false).unwrap();
+            let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
+            let normals = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap();
+            for (position, normal) in positions
+                .as_float3()
+                .unwrap()
+                .iter()
+                .zip(normals.as_float3().unwrap())
+            {
+                assert!(normal[0] == 0.0 || position[0] != boundary);
+            }
+        }
+
+        unload(&mut world, IVec2::X);
+        assert!(world.stale_meshes.contains(&IVec2::ZERO));
+    }
+
+    #[test]
+    fn placement_lands_on_the_players_side_of_a_wall() {
+        let mut map = HashMap::new();
+        for y in 0..6 {
+            for z in -2..=2 {
+                map.insert(IVec3::new(5, y, z), BlockType::Stone);
+            }
+        }
+        let eye = Vec3::new(1.0, 3.0, 0.0);
+
+        let hit = raycast_block(&map, eye, Vec3::X, REACH_DISTANCE).unwrap();
+        assert_eq!(hit.cell, IVec3::new(5, 3, 0));
+        assert_eq!(hit.normal, IVec3::NEG_X);
+
+        let place = hit.cell + hit.normal;
+        assert_eq!(place, IVec3::new(4, 3, 0));
+        assert!(!map.contains_key(&place));
+        assert!(!block_overlaps_player(place, eye));
+    }
+
+    #[test]
+    fn placement_inside_the_player_is_refused() {
+        let eye = Vec3::new(1.0, 3.0, 0.0);
+        for y in 1..=3 {
+            assert!(block_overlaps_player(IVec3::new(1, y, 0), eye));
+        }
+        assert!(!block_overlaps_player(IVec3::new(1, 0, 0), eye));
+        assert!(!block_overlaps_player(IVec3::new(1, 4, 0), eye));
+        assert!(!block_overlaps_player(IVec3::new(2, 3, 0), eye));
+    }
+
+    fn mesh_of(cells: &[IVec3]) -> Mesh {
+        let map: HashMap<IVec3, BlockType> =
+            cells.iter().map(|&cell| (cell, BlockType::Stone)).collect();
+        build_chunk_mesh(&map, cells, false).unwrap()
+    }
+
+    fn mesh_positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
+        positions.as_float3().unwrap().to_vec()
+    }
+
+    #[test]
+    fn single_block_meshes_all_six_faces() {
+        let mesh = mesh_of(&[IVec3::ZERO]);
+        let positions = mesh_positions(&mesh);
+
+        assert_eq!(positions.len(), 24);
+        assert_eq!(mesh.indices().unwrap().len(), 36);
+        assert!(positions
+            .iter()
+            .all(|position| position.iter().all(|c| c.abs() == 0.5)));
+        assert!(positions.contains(&[0.5, 0.5, 0.5]));
+        assert!(positions.contains(&[-0.5, -0.5, -0.5]));
+    }
+
+    #[test]
+    fn adjacent_blocks_drop_their_shared_faces() {
+        let mesh = mesh_of(&[IVec3::ZERO, IVec3::X]);
+        let positions = mesh_positions(&mesh);
+
+        assert_eq!(positions.len(), 40);
+        assert_eq!(mesh.indices().unwrap().len(), 60);
+        assert!(positions.contains(&[1.5, 0.5, 0.5]));
+        assert!(positions.contains(&[-0.5, -0.5, -0.5]));
+    }
+}